@@ -1,7 +1,14 @@
 use proc_macro::{Span, TokenStream};
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use syn::{meta::ParseNestedMeta, parse_macro_input, Error, ItemFn, LitStr, Result};
 
+/// `SocialIntegration_vN` ABI generations this crate has bindings and an [`ApiVersion`] impl
+/// for, newest last. [`impl_init`] emits a `GetInfo`/`Init` symbol pair for each one, so a
+/// single compiled plugin keeps loading across OpenTTD releases that bump the API.
+///
+/// [`ApiVersion`]: ../openttd_social_integration_api/trait.ApiVersion.html
+const KNOWN_API_VERSIONS: &[u32] = &[1];
+
 #[derive(Default)]
 struct Attributes {
     social_platform: Option<LitStr>,
@@ -44,22 +51,19 @@ impl Attributes {
 ///
 /// Returning `Ok(None)` means the Social Platform is not running.
 ///
-/// Returning `Err(())` means the plugin failed to initialize (generic error).
+/// Returning `Err(InitError::Failed)` (or any other `InitError` variant) means the plugin
+/// failed to initialize; OpenTTD surfaces the specific variant in its UI.
 /// # Examples
 /// ```no_run
-/// use openttd_social_integration_api::{PluginApi, OpenTTDInfo};
+/// use openttd_social_integration_api::{InitError, OpenTTDInfo, SocialPlugin};
+///
+/// struct MyPlugin;
+///
+/// impl SocialPlugin for MyPlugin {}
 ///
 /// #[openttd_social_integration_api_macros::init(platform = "test", name = "Test Plugin", version = "0.1")]
-/// pub fn init(info: OpenTTDInfo) -> Result<Option<PluginApi>, ()> {
-///     Ok(Some(PluginApi {
-///         shutdown: None,
-///         run_callbacks: None,
-///         event_enter_main_menu: None,
-///         event_enter_scenario_editor: None,
-///         event_enter_singleplayer: None,
-///         event_enter_multiplayer: None,
-///         event_joining_multiplayer: None
-///     }))
+/// pub fn init(info: OpenTTDInfo) -> Result<Option<Box<dyn SocialPlugin>>, InitError> {
+///     Ok(Some(Box::new(MyPlugin)))
 /// }
 /// ```
 #[proc_macro_attribute]
@@ -88,29 +92,40 @@ fn impl_init(attrs: Attributes, ast: &syn::ItemFn) -> TokenStream {
     let platform = attrs.social_platform.unwrap();
     let plugin_name = attrs.name.unwrap();
     let version = attrs.version.unwrap();
-    let mut gen = quote! {
-        #[no_mangle]
-        pub unsafe extern "C" fn SocialIntegration_v1_GetInfo(plugin_info: *mut ::openttd_social_integration_api::raw_api::OpenTTD_SocialIntegration_v1_PluginInfo) {
-            *plugin_info = ::openttd_social_integration_api::raw_api::OpenTTD_SocialIntegration_v1_PluginInfo {
-                social_platform: stringify!(#platform).as_ptr().cast(),
-                name: stringify!(#plugin_name).as_ptr().cast(),
-                version: stringify!(#version).as_ptr().cast(),
-            };
-        }
 
-        #[no_mangle]
-        pub unsafe extern "C" fn SocialIntegration_v1_Init(
-            plugin_api: *mut ::openttd_social_integration_api::raw_api::OpenTTD_SocialIntegration_v1_PluginApi,
-            openttd_info: *const ::openttd_social_integration_api::raw_api::OpenTTD_SocialIntegration_v1_OpenTTDInfo,
-        ) -> ::openttd_social_integration_api::raw_api::OpenTTD_SocialIntegration_v1_InitResult {
-            let ret = unsafe { ::openttd_social_integration_api::call_init(#name, openttd_info) };
-            match ret.0 {
-                Some(api) => *plugin_api = api,
-                None => {}
+    let mut gen = quote! {};
+    for api_version in KNOWN_API_VERSIONS {
+        let get_info_fn = format_ident!("SocialIntegration_v{}_GetInfo", api_version);
+        let init_fn = format_ident!("SocialIntegration_v{}_Init", api_version);
+        let plugin_info_ty = format_ident!("OpenTTD_SocialIntegration_v{}_PluginInfo", api_version);
+        let version_marker = format_ident!("V{}", api_version);
+
+        gen.extend(quote! {
+            #[no_mangle]
+            pub unsafe extern "C" fn #get_info_fn(plugin_info: *mut ::openttd_social_integration_api::raw_api::#plugin_info_ty) {
+                *plugin_info = ::openttd_social_integration_api::raw_api::#plugin_info_ty {
+                    social_platform: stringify!(#platform).as_ptr().cast(),
+                    name: stringify!(#plugin_name).as_ptr().cast(),
+                    version: stringify!(#version).as_ptr().cast(),
+                };
             }
-            return ret.1;
-        }
-    };
+
+            #[no_mangle]
+            pub unsafe extern "C" fn #init_fn(
+                plugin_api: *mut <::openttd_social_integration_api::#version_marker as ::openttd_social_integration_api::ApiVersion>::RawPluginApi,
+                openttd_info: *const <::openttd_social_integration_api::#version_marker as ::openttd_social_integration_api::ApiVersion>::RawOpenTTDInfo,
+            ) -> <::openttd_social_integration_api::#version_marker as ::openttd_social_integration_api::ApiVersion>::RawInitResult {
+                let ret = unsafe {
+                    <::openttd_social_integration_api::#version_marker as ::openttd_social_integration_api::ApiVersion>::call_init(#name, openttd_info)
+                };
+                match ret.0 {
+                    Some(api) => *plugin_api = api,
+                    None => {}
+                }
+                return ret.1;
+            }
+        });
+    }
     gen.extend(ast.to_token_stream());
     gen.into()
 }
@@ -0,0 +1,117 @@
+//! Discord Rich Presence integration, built on top of [`SocialPlugin`].
+//!
+//! Enable the `discord` feature and link this module's [`init`] function (or copy it as a
+//! starting point) to get working Discord presence without touching the IPC protocol
+//! yourself. It connects to the local Discord client on init, reports
+//! [`Ok(None)`](Result::Ok) when Discord isn't running, and turns the game-state callbacks
+//! into presence payloads.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use discord_rich_presence::{
+    activity::{Activity, Assets, Timestamps},
+    DiscordIpc, DiscordIpcClient,
+};
+
+use crate::{InitError, OpenTTDInfo, SocialPlugin};
+
+/// Discord application ID OpenTTD's presence is reported under.
+///
+/// Plugin authors shipping their own Discord integration should register their own application
+/// at <https://discord.com/developers/applications> and swap this out.
+const DISCORD_APPLICATION_ID: &str = "1149764972423379065";
+
+/// How many consecutive failed `reconnect()` attempts `run_callbacks` tolerates before giving
+/// up and asking OpenTTD to unload the plugin.
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// A [`SocialPlugin`] that reports the player's current activity to Discord Rich Presence.
+pub struct DiscordPlugin {
+    client: DiscordIpcClient,
+    start_time: i64,
+    /// Set by [`Self::set_presence`] when a presence update fails, so `run_callbacks` knows to
+    /// try to recover the connection instead of leaving a healthy one alone.
+    disconnected: bool,
+    /// Consecutive failed `reconnect()` attempts since `disconnected` was last set.
+    reconnect_attempts: u32,
+}
+
+impl DiscordPlugin {
+    fn set_presence(&mut self, state: &str, details: Option<&str>) {
+        let mut activity = Activity::new()
+            .state(state)
+            .assets(Assets::new().large_image("openttd"))
+            .timestamps(Timestamps::new().start(self.start_time));
+        if let Some(details) = details {
+            activity = activity.details(details);
+        }
+        // Presence updates are best-effort; a dropped IPC connection is picked up and reported
+        // by `run_callbacks` on the next tick.
+        self.disconnected = self.client.set_activity(activity).is_err();
+    }
+}
+
+impl SocialPlugin for DiscordPlugin {
+    fn shutdown(&mut self) {
+        let _ = self.client.close();
+    }
+
+    fn run_callbacks(&mut self) -> bool {
+        // discord-rich-presence has no separate callback queue to pump; leave a healthy
+        // connection alone and only retry the handshake once a presence update has actually
+        // failed, so a relaunched Discord client is picked back up without tearing down a
+        // working connection every tick.
+        if !self.disconnected {
+            return true;
+        }
+        if self.client.reconnect().is_ok() {
+            self.disconnected = false;
+            self.reconnect_attempts = 0;
+            return true;
+        }
+        self.reconnect_attempts += 1;
+        self.reconnect_attempts < MAX_RECONNECT_ATTEMPTS
+    }
+
+    fn event_enter_main_menu(&mut self) {
+        self.set_presence("In the main menu", None);
+    }
+
+    fn event_enter_scenario_editor(&mut self, map_width: u32, map_height: u32) {
+        self.set_presence("In the Scenario Editor", Some(&format!("{}\u{d7}{}", map_width, map_height)));
+    }
+
+    fn event_enter_singleplayer(&mut self, map_width: u32, map_height: u32) {
+        self.set_presence("Playing singleplayer", Some(&format!("{}\u{d7}{}", map_width, map_height)));
+    }
+
+    fn event_enter_multiplayer(&mut self, map_width: u32, map_height: u32) {
+        self.set_presence("In multiplayer", Some(&format!("{}\u{d7}{}", map_width, map_height)));
+    }
+
+    fn event_joining_multiplayer(&mut self) {
+        self.set_presence("Joining multiplayer", None);
+    }
+}
+
+/// Connects to the local Discord client and starts reporting presence.
+///
+/// Returns `Ok(None)` if Discord isn't running, so OpenTTD can silently skip loading the
+/// plugin rather than reporting an error.
+#[openttd_social_integration_api_macros::init(platform = "Discord", name = "Discord Rich Presence", version = "0.1")]
+pub fn init(_info: OpenTTDInfo) -> Result<Option<Box<dyn SocialPlugin>>, InitError> {
+    let mut client = DiscordIpcClient::new(DISCORD_APPLICATION_ID).map_err(|_| InitError::Failed)?;
+    if client.connect().is_err() {
+        return Ok(None);
+    }
+    let start_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| InitError::Failed)?
+        .as_secs() as i64;
+    Ok(Some(Box::new(DiscordPlugin {
+        client,
+        start_time,
+        disconnected: false,
+        reconnect_attempts: 0,
+    })))
+}
@@ -1,64 +1,98 @@
 //! Unofficial Rust binding for OpenTTD Social Integration API.
-//! 
+//!
 //! Use [`openttd_social_integration_api_macros::init`] for entrypoint.
-//! 
+//!
+//! Enable the `sign` feature and see [`sign`] to produce the `.sig` file OpenTTD requires
+//! next to a built plugin.
+//!
+//! Enable the `discord` feature for a ready-made Discord Rich Presence integration, see
+//! [`discord`].
+//!
+//! A plugin is written once against [`SocialPlugin`]; [`init`](openttd_social_integration_api_macros::init)
+//! emits the `SocialIntegration_vN_GetInfo`/`_Init` symbol pair for every ABI generation this
+//! crate has bindings for (see [`ApiVersion`]), so the same compiled plugin keeps loading
+//! across OpenTTD releases that bump the Social Integration API.
+//!
 //! # Examples
 //! ```no_run
-//! use openttd_social_integration_api::{OpenTTDInfo, PluginApi};
-//! 
-//! fn shutdown() {
-//!     println!("Shutting down!");
+//! use openttd_social_integration_api::{InitError, OpenTTDInfo, SocialPlugin};
+//!
+//! struct ExamplePlugin;
+//!
+//! impl SocialPlugin for ExamplePlugin {
+//!     fn shutdown(&mut self) {
+//!         println!("Shutting down!");
+//!     }
+//!
+//!     fn run_callbacks(&mut self) -> bool {
+//!         // This println will make log full of garbage.
+//!         return true;
+//!     }
+//!
+//!     fn event_enter_main_menu(&mut self) {
+//!         println!("Entering main menu!");
+//!     }
+//!
+//!     fn event_enter_scenario_editor(&mut self, map_width: u32, map_height: u32) {
+//!         println!("Entering scenario editor ({}x{})!", map_width, map_height);
+//!     }
+//!
+//!     fn event_enter_singleplayer(&mut self, map_width: u32, map_height: u32) {
+//!         println!("Entering singleplayer ({}x{})!", map_width, map_height);
+//!     }
+//!
+//!     fn event_enter_multiplayer(&mut self, map_width: u32, map_height: u32) {
+//!         println!("Entering multiplayer ({}x{})!", map_width, map_height);
+//!     }
+//!
+//!     fn event_joining_multiplayer(&mut self) {
+//!         println!("Joining multiplayer!");
+//!     }
 //! }
-//! 
-//! fn run_callbacks() -> bool {
-//!     // This println will make log full of garbage.
-//!     return true;
-//! }
-//! 
-//! fn event_enter_main_menu() {
-//!     println!("Entering main menu!");
-//! }
-//! 
-//! fn event_enter_scenario_editor(map_width: u32, map_height: u32) {
-//!     println!("Entering scenario editor ({}x{})!", map_width, map_height);
-//! }
-//! 
-//! fn event_enter_singleplayer(map_width: u32, map_height: u32) {
-//!     println!("Entering singleplayer ({}x{})!", map_width, map_height);
-//! }
-//! 
-//! fn event_enter_multiplayer(map_width: u32, map_height: u32) {
-//!     println!("Entering multiplayer ({}x{})!", map_width, map_height);
-//! }
-//! 
-//! fn event_joining_multiplayer() {
-//!     println!("Joining multiplayer!");
-//! }
-//! 
+//!
 //! #[openttd_social_integration_api_macros::init(platform = "test", name = "Test Plugin", version = "0.1")]
-//! pub fn init(info: OpenTTDInfo) -> Result<Option<PluginApi>, ()> {
+//! pub fn init(info: OpenTTDInfo) -> Result<Option<Box<dyn SocialPlugin>>, InitError> {
 //!     println!("Init for OpenTTD {}", info.openttd_version);
-//!     Ok(Some(PluginApi {
-//!         shutdown: Some(shutdown),
-//!         run_callbacks: Some(run_callbacks),
-//!         event_enter_main_menu: Some(event_enter_main_menu),
-//!         event_enter_scenario_editor: Some(event_enter_scenario_editor),
-//!         event_enter_singleplayer: Some(event_enter_singleplayer),
-//!         event_enter_multiplayer: Some(event_enter_multiplayer),
-//!         event_joining_multiplayer: Some(event_joining_multiplayer)
-//!     }))
+//!     Ok(Some(Box::new(ExamplePlugin)))
 //! }
-//! 
+//!
 //! ```
 
+// The `#[init]` macro always expands to absolute `::openttd_social_integration_api::...`
+// paths, since that's the only way it can name this crate's items from a downstream plugin
+// crate. The `discord` module uses `#[init]` on its own `init` function, so this crate needs
+// to resolve its own absolute path too.
+extern crate self as openttd_social_integration_api;
+
 pub mod raw_api;
+#[cfg(feature = "discord")]
+pub mod discord;
+#[cfg(feature = "sign")]
+pub mod sign;
+
+use std::{
+    ffi::CStr,
+    sync::{Mutex, OnceLock},
+};
 
-use std::ffi::CStr;
+use crate::raw_api::{OpenTTD_SocialIntegration_v1_InitResult, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_DUPLICATE, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_FAILED, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_INVALID_SIGNATURE, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_PLATFORM_NOT_RUNNING, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_SUCCESS, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_UNSUPPORTED_API, OpenTTD_SocialIntegration_v1_OpenTTDInfo, OpenTTD_SocialIntegration_v1_PluginApi};
 
-use crate::raw_api::{OpenTTD_SocialIntegration_v1_InitResult, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_FAILED, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_PLATFORM_NOT_RUNNING, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_SUCCESS, OpenTTD_SocialIntegration_v1_OpenTTDInfo, OpenTTD_SocialIntegration_v1_PluginApi};
+/// Why a plugin's `init` failed, mirroring the states OpenTTD's own
+/// `SocialIntegrationPlugin::State` surfaces in its UI.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InitError {
+    /// Generic initialization failure.
+    Failed,
+    /// The plugin doesn't support the OpenTTD API version it was loaded against.
+    UnsupportedApi,
+    /// Another plugin is already active for this Social Platform.
+    Duplicate,
+    /// The plugin's `.sig` signature file failed to validate.
+    InvalidSignature,
+}
 
 /// Pointers supplied by OpenTTD, for the plugin to use.
-/// 
+///
 /// Package to [raw_api::OpenTTD_SocialIntegration_v1_OpenTTDInfo]
 pub struct OpenTTDInfo {
     /// Version of OpenTTD
@@ -71,112 +105,210 @@ impl From<OpenTTD_SocialIntegration_v1_OpenTTDInfo> for OpenTTDInfo {
     }
 }
 
-/// Pointers supplied by the plugin for OpenTTD to use.
-/// 
-/// Package to [raw_api::OpenTTD_SocialIntegration_v1_PluginApi]
-#[derive(Debug, Copy, Clone)]
-pub struct PluginApi {
+/// A running plugin instance, kept alive between callbacks.
+///
+/// Implement this for whatever state your integration needs to carry between calls (an SDK
+/// handle, a connection, cached presence data, ...), return a boxed instance from your `init`
+/// function, and OpenTTD will call these methods on it for as long as the plugin stays loaded.
+/// Every method has a no-op default, so a plugin only needs to override the callbacks it cares
+/// about.
+///
+/// `Send` is required because the static that holds the boxed trait object is shared behind a
+/// `Mutex` and must be `Sync`, which in turn requires the `Mutex`'s contents to be `Send`.
+pub trait SocialPlugin: Send {
     /// OpenTTD tells the plugin to shut down.
-    /// 
-    /// The plugin should free any resources it allocated, and must not call any of the callback functions after this call.
-    pub shutdown: Option<fn()>,
+    ///
+    /// The plugin should free any resources it allocated, and must not call any of the
+    /// OpenTTD_SocialIntegrationCallbacks functions after this call.
+    fn shutdown(&mut self) {}
 
-    /// OpenTTD calls this function at regular intervals, to handle any callbacks the plugin might have.
-    /// 
+    /// OpenTTD calls this function at regular intervals, to handle any callbacks the plugin
+    /// might have.
+    ///
     /// It is also safe to call the OpenTTD_SocialIntegrationCallbacks functions here.
-    /// 
-    /// If the plugin wants to be called again, please return `true`. Return `false` if the plugin wants to be unloaded.
-    pub run_callbacks: Option<fn() -> bool>,
+    ///
+    /// If the plugin wants to be called again, please return `true`. Return `false` if the
+    /// plugin wants to be unloaded.
+    fn run_callbacks(&mut self) -> bool { true }
 
     /// The player has entered the main menu.
-    pub event_enter_main_menu: Option<fn()>,
-    
+    fn event_enter_main_menu(&mut self) {}
+
     /// The player has entered the Scenario Editor.
-    /// 
+    ///
     /// `map_width` is the width of the map in tiles.
     /// `map_height` is the height of the map in tiles.
-    pub event_enter_scenario_editor: Option<fn(map_width: u32, map_height: u32)>,
+    fn event_enter_scenario_editor(&mut self, map_width: u32, map_height: u32) { let _ = (map_width, map_height); }
+
     /// The player has entered a singleplayer game.
-    /// 
+    ///
     /// `map_width` is the width of the map in tiles.
     /// `map_height` is the height of the map in tiles.
-    pub event_enter_singleplayer: Option<fn(map_width: u32, map_height: u32)>,
+    fn event_enter_singleplayer(&mut self, map_width: u32, map_height: u32) { let _ = (map_width, map_height); }
+
     /// The player has entered a multiplayer game.
-    /// 
+    ///
     /// `map_width` is the width of the map in tiles.
     /// `map_height` is the height of the map in tiles.
-    pub event_enter_multiplayer: Option<fn(map_width: u32, map_height: u32)>,
+    fn event_enter_multiplayer(&mut self, map_width: u32, map_height: u32) { let _ = (map_width, map_height); }
+
     /// The player is joining a multiplayer game.
-    /// 
+    ///
     /// This is followed by event_enter_multiplayer() if the join was successful.
+    fn event_joining_multiplayer(&mut self) {}
+}
+
+/// Pointers supplied by the plugin for OpenTTD to use.
+///
+/// A thin adapter kept for plugins that would rather hand over bare callback functions than
+/// implement [`SocialPlugin`] directly. It implements [`SocialPlugin`] itself, so it can be
+/// boxed and returned from `init` like any other plugin state.
+///
+/// Package to [raw_api::OpenTTD_SocialIntegration_v1_PluginApi]
+#[derive(Debug, Copy, Clone)]
+pub struct PluginApi {
+    /// See [`SocialPlugin::shutdown`].
+    pub shutdown: Option<fn()>,
+
+    /// See [`SocialPlugin::run_callbacks`].
+    pub run_callbacks: Option<fn() -> bool>,
+
+    /// See [`SocialPlugin::event_enter_main_menu`].
+    pub event_enter_main_menu: Option<fn()>,
+
+    /// See [`SocialPlugin::event_enter_scenario_editor`].
+    pub event_enter_scenario_editor: Option<fn(map_width: u32, map_height: u32)>,
+    /// See [`SocialPlugin::event_enter_singleplayer`].
+    pub event_enter_singleplayer: Option<fn(map_width: u32, map_height: u32)>,
+    /// See [`SocialPlugin::event_enter_multiplayer`].
+    pub event_enter_multiplayer: Option<fn(map_width: u32, map_height: u32)>,
+    /// See [`SocialPlugin::event_joining_multiplayer`].
     pub event_joining_multiplayer: Option<fn()>,
 }
 
-static mut PLUGIN_API: PluginApi = PluginApi {
-    shutdown: None,
-    run_callbacks: None,
-    event_enter_main_menu: None,
-    event_enter_scenario_editor: None,
-    event_enter_singleplayer: None,
-    event_enter_multiplayer: None,
-    event_joining_multiplayer: None,
-};
+impl SocialPlugin for PluginApi {
+    fn shutdown(&mut self) {
+        if let Some(f) = self.shutdown { f() }
+    }
+
+    fn run_callbacks(&mut self) -> bool {
+        self.run_callbacks.map_or(true, |f| f())
+    }
+
+    fn event_enter_main_menu(&mut self) {
+        if let Some(f) = self.event_enter_main_menu { f() }
+    }
+
+    fn event_enter_scenario_editor(&mut self, map_width: u32, map_height: u32) {
+        if let Some(f) = self.event_enter_scenario_editor { f(map_width, map_height) }
+    }
+
+    fn event_enter_singleplayer(&mut self, map_width: u32, map_height: u32) {
+        if let Some(f) = self.event_enter_singleplayer { f(map_width, map_height) }
+    }
+
+    fn event_enter_multiplayer(&mut self, map_width: u32, map_height: u32) {
+        if let Some(f) = self.event_enter_multiplayer { f(map_width, map_height) }
+    }
+
+    fn event_joining_multiplayer(&mut self) {
+        if let Some(f) = self.event_joining_multiplayer { f() }
+    }
+}
+
+/// The live plugin instance, set once by [`ApiVersion::call_init`] and shared with the callback thunks.
+///
+/// OpenTTD only ever calls these from the thread that called `SocialIntegration_v1_Init`, but
+/// the `Mutex` keeps access sound without relying on that being documented guaranteed behaviour.
+static PLUGIN: OnceLock<Mutex<Box<dyn SocialPlugin>>> = OnceLock::new();
+
+fn plugin() -> &'static Mutex<Box<dyn SocialPlugin>> {
+    PLUGIN.get().expect("callback invoked before a successful SocialIntegration_v1_Init")
+}
 
 unsafe extern "C" fn shutdown() {
-    PLUGIN_API.shutdown.unwrap()();
+    plugin().lock().unwrap().shutdown();
 }
 
 unsafe extern "C" fn run_callbacks() -> bool {
-    PLUGIN_API.run_callbacks.unwrap()()
+    plugin().lock().unwrap().run_callbacks()
 }
 
 unsafe extern "C" fn event_enter_main_menu() {
-    PLUGIN_API.event_enter_main_menu.unwrap()();
+    plugin().lock().unwrap().event_enter_main_menu();
 }
 
 unsafe extern "C" fn event_enter_scenario_editor(map_width: u32, map_height: u32) {
-    PLUGIN_API.event_enter_scenario_editor.unwrap()(map_width, map_height);
+    plugin().lock().unwrap().event_enter_scenario_editor(map_width, map_height);
 }
 
 unsafe extern "C" fn event_enter_singleplayer(map_width: u32, map_height: u32) {
-    PLUGIN_API.event_enter_singleplayer.unwrap()(map_width, map_height);
+    plugin().lock().unwrap().event_enter_singleplayer(map_width, map_height);
 }
 
 unsafe extern "C" fn event_enter_multiplayer(map_width: u32, map_height: u32) {
-    PLUGIN_API.event_enter_multiplayer.unwrap()(map_width, map_height);
+    plugin().lock().unwrap().event_enter_multiplayer(map_width, map_height);
 }
 
 unsafe extern "C" fn event_joining_multiplayer() {
-    PLUGIN_API.event_joining_multiplayer.unwrap()();
+    plugin().lock().unwrap().event_joining_multiplayer();
 }
 
-macro_rules! wrapper_some {
-    ($x : ident) => {
-        match PLUGIN_API.$x {
-            Some(_) => Some($x),
-            None => None
+/// Glue between one `SocialIntegration_vN` ABI generation's raw C types and the crate's
+/// version-agnostic [`SocialPlugin`]/[`InitError`] surface.
+///
+/// The [`init`](../openttd_social_integration_api_macros/attr.init.html) macro emits a
+/// `SocialIntegration_vN_GetInfo`/`_Init` symbol pair for every version number in its
+/// `KNOWN_API_VERSIONS` list, dispatching each pair through the `VN` type's impl of this
+/// trait, so a single compiled plugin keeps loading across OpenTTD releases that bump the
+/// Social Integration API. A plugin written once against [`SocialPlugin`] is reused for each
+/// version; adding support for a new generation means adding its raw bindings to
+/// [`raw_api`], a `VN` type with an `ApiVersion` impl here, and `N` to the macro's
+/// `KNOWN_API_VERSIONS`, without touching existing plugins.
+pub trait ApiVersion {
+    /// Raw `OpenTTD_SocialIntegration_vN_OpenTTDInfo` for this version.
+    type RawOpenTTDInfo;
+    /// Raw `OpenTTD_SocialIntegration_vN_PluginApi` for this version.
+    type RawPluginApi;
+    /// Raw `OpenTTD_SocialIntegration_vN_InitResult` for this version.
+    type RawInitResult;
+
+    /// Internal function. Used by proc macro. Don't use in your code.
+    unsafe fn call_init<F>(init: F, info: *const Self::RawOpenTTDInfo) -> (Option<Self::RawPluginApi>, Self::RawInitResult)
+        where F: FnOnce(OpenTTDInfo) -> Result<Option<Box<dyn SocialPlugin>>, InitError>;
+}
+
+/// The `SocialIntegration_v1` ABI generation — the only one OpenTTD speaks today.
+pub struct V1;
+
+impl ApiVersion for V1 {
+    type RawOpenTTDInfo = OpenTTD_SocialIntegration_v1_OpenTTDInfo;
+    type RawPluginApi = OpenTTD_SocialIntegration_v1_PluginApi;
+    type RawInitResult = OpenTTD_SocialIntegration_v1_InitResult;
+
+    unsafe fn call_init<F>(init: F, info: *const Self::RawOpenTTDInfo) -> (Option<Self::RawPluginApi>, Self::RawInitResult)
+        where F: FnOnce(OpenTTDInfo) -> Result<Option<Box<dyn SocialPlugin>>, InitError>
+    {
+        match init((*info).into()) {
+            Ok(Some(plugin_instance)) => {
+                // PLUGIN is set at most once per process and is not cleared by shutdown; if it
+                // is already set, this new instance is dropped and the first one stays in use.
+                let _ = PLUGIN.set(Mutex::new(plugin_instance));
+                (Some(OpenTTD_SocialIntegration_v1_PluginApi {
+                    shutdown: Some(shutdown),
+                    run_callbacks: Some(run_callbacks),
+                    event_enter_main_menu: Some(event_enter_main_menu),
+                    event_enter_scenario_editor: Some(event_enter_scenario_editor),
+                    event_enter_singleplayer: Some(event_enter_singleplayer),
+                    event_enter_multiplayer: Some(event_enter_multiplayer),
+                    event_joining_multiplayer: Some(event_joining_multiplayer),
+                }), OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_SUCCESS)
+            },
+            Ok(None) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_PLATFORM_NOT_RUNNING),
+            Err(InitError::Failed) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_FAILED),
+            Err(InitError::UnsupportedApi) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_UNSUPPORTED_API),
+            Err(InitError::Duplicate) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_DUPLICATE),
+            Err(InitError::InvalidSignature) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_INVALID_SIGNATURE),
         }
-    };
-}
-
-/// Internal function. Used by proc macro. Don't use in your code.
-pub unsafe fn call_init<F> (init: F, info: *const OpenTTD_SocialIntegration_v1_OpenTTDInfo) -> (Option<OpenTTD_SocialIntegration_v1_PluginApi>, OpenTTD_SocialIntegration_v1_InitResult)
-    where F: FnOnce(OpenTTDInfo) -> Result<Option<PluginApi>, ()>
-{
-    match init((*info).into()) {
-        Ok(Some(api)) => {
-            PLUGIN_API = api;
-            (Some(OpenTTD_SocialIntegration_v1_PluginApi {
-                shutdown: wrapper_some!(shutdown),
-                run_callbacks: wrapper_some!(run_callbacks),
-                event_enter_main_menu: wrapper_some!(event_enter_main_menu),
-                event_enter_scenario_editor: wrapper_some!(event_enter_scenario_editor),
-                event_enter_singleplayer: wrapper_some!(event_enter_singleplayer),
-                event_enter_multiplayer: wrapper_some!(event_enter_multiplayer),
-                event_joining_multiplayer: wrapper_some!(event_joining_multiplayer),
-            }), OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_SUCCESS)
-        },
-        Ok(None) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_PLATFORM_NOT_RUNNING),
-        Err(_) => (None, OpenTTD_SocialIntegration_v1_InitResult_OTTD_SOCIAL_INTEGRATION_V1_INIT_FAILED),
     }
 }
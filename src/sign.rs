@@ -0,0 +1,66 @@
+//! Produces the detached `.sig` files OpenTTD requires next to a plugin's shared library.
+//!
+//! OpenTTD's loader refuses to load a plugin unless `ValidateSignatureFile` succeeds against
+//! a `<filename>.sig` file sitting next to the compiled `cdylib`. That file is the raw 64-byte
+//! Ed25519 signature (no PEM, no length prefix) of the library's bytes, verified against a
+//! public key the plugin author has registered with OpenTTD. This module signs with
+//! [`ed25519-dalek`](https://docs.rs/ed25519-dalek), which is wire-compatible with the
+//! monocypher Ed25519 implementation the reference loader uses.
+//!
+//! # Usage
+//! Generate a keypair once with [`generate_keypair`], register the public key as documented
+//! on the [wiki](https://wiki.openttd.org/en/Development/Social%20Integration), and keep the
+//! secret key private. Then sign the built `cdylib` by calling [`sign`] from a small
+//! `xtask`/CI script run *after* `cargo build` has produced the library — a crate's own
+//! `build.rs` runs before that crate is compiled, so there is no `cdylib` on disk yet for a
+//! `build.rs` to sign:
+//!
+//! ```no_run
+//! use openttd_social_integration_api::sign::sign;
+//! use ed25519_dalek::SigningKey;
+//!
+//! # fn example(secret_key: &SigningKey) -> std::io::Result<()> {
+//! sign("target/release/libmy_plugin.so", secret_key)?;
+//! // Writes target/release/libmy_plugin.so.sig
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The resulting `.sig` file must be placed next to the `cdylib` in the `social_integration`
+//! folder OpenTTD scans for plugins.
+
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand_core::OsRng;
+
+/// Generates a new Ed25519 keypair for signing plugin builds.
+///
+/// The returned [`SigningKey`] must be kept secret; the [`VerifyingKey`] is the public key to
+/// register with OpenTTD so it can validate the plugin's `.sig` files.
+pub fn generate_keypair() -> (SigningKey, VerifyingKey) {
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let verifying_key = signing_key.verifying_key();
+    (signing_key, verifying_key)
+}
+
+/// Signs the compiled `cdylib` at `library_path` and writes the detached signature to
+/// `<library_path>.sig`, in the raw 64-byte layout OpenTTD's loader expects.
+///
+/// Returns the path of the written `.sig` file.
+pub fn sign(library_path: impl AsRef<Path>, secret_key: &SigningKey) -> io::Result<PathBuf> {
+    let library_path = library_path.as_ref();
+    let library_bytes = fs::read(library_path)?;
+    let signature: Signature = secret_key.sign(&library_bytes);
+
+    let mut sig_path = library_path.as_os_str().to_owned();
+    sig_path.push(".sig");
+    let sig_path = PathBuf::from(sig_path);
+
+    fs::write(&sig_path, signature.to_bytes())?;
+    Ok(sig_path)
+}